@@ -13,32 +13,45 @@ use thiserror::Error;
 use axum_macros::debug_handler;
 use dotenv::dotenv;
 use ethers::{
-    core::types::{serde_helpers::Numeric, Address, Eip1559TransactionRequest},
+    core::types::{serde_helpers::Numeric, Address, Eip1559TransactionRequest, TransactionRequest},
     middleware::{nonce_manager::NonceManagerMiddleware, signer::SignerMiddleware},
-    providers::{Http, Provider},
+    providers::{
+        Http, HttpRateLimitRetryPolicy, Provider, Quorum, QuorumProvider, QuorumProviderBuilder,
+        RetryClient, RetryClientBuilder, WeightedProvider,
+    },
     signers::{LocalWallet, Signer},
-    types::Chain,
+    types::{
+        transaction::{
+            eip2718::TypedTransaction,
+            eip2930::{AccessList, Eip2930TransactionRequest},
+        },
+        Chain,
+    },
 };
 
 use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::{mysql::MySqlPoolOptions, MySql, Pool};
-use std::{env, fmt, net::SocketAddr, str::FromStr, sync::Arc};
+use std::{env, fmt, net::SocketAddr, str::FromStr, sync::Arc, time::Duration};
 use tracing::{info, Level};
 use uuid::Uuid;
 
 mod transaction_monitor;
-pub use transaction_monitor::TransactionMonitor;
+pub use transaction_monitor::{supports_eip1559, BlockSource, Speed, TransactionMonitor, TxKind};
 
 mod alchemy_rpc;
-pub use alchemy_rpc::get_rpc;
+pub use alchemy_rpc::{get_rpc, get_ws_rpc};
 
-type ConfigedProvider = NonceManagerMiddleware<SignerMiddleware<Provider<Http>, LocalWallet>>;
+type ConfigedProvider =
+    NonceManagerMiddleware<SignerMiddleware<Provider<QuorumProvider<RetryClient<Http>>>, LocalWallet>>;
 type ConfigedMonitor = TransactionMonitor<ConfigedProvider>;
 
 #[derive(Debug, Clone)]
 struct AppState {
     monitor: Arc<ConfigedMonitor>,
     config: Arc<Config>,
+    // which envelope to use when a caller doesn't specify `kind` explicitly,
+    // chosen once at startup based on whether `chain` supports EIP-1559
+    default_tx_kind: TxKind,
 }
 
 #[derive(Debug, Clone)]
@@ -48,6 +61,29 @@ struct Config {
     alchemy_key: String,
     database_url: String,
     port: u16,
+    fee_history_block_window: u64,
+    fee_history_percentile: f64,
+    rpc_max_retries: u32,
+    rpc_initial_backoff_ms: u64,
+    // explicit websocket endpoint; takes priority over `use_alchemy_ws`
+    wss_url: Option<String>,
+    // derive the websocket endpoint from `alchemy_key` instead, the same way
+    // the HTTP endpoint is derived via `get_rpc`
+    use_alchemy_ws: bool,
+    ws_initial_backoff_ms: u64,
+    ws_max_backoff_ms: u64,
+    // extra RPC endpoints to fan reads/sends out to alongside the primary
+    // Alchemy one, for quorum-checked submission and inclusion checks
+    extra_rpc_urls: Vec<String>,
+    // how many of the configured endpoints must agree before a result (or a
+    // `send_transaction`) is accepted; e.g. 2 for "2-of-3"
+    rpc_quorum_threshold: usize,
+    // max pending txs tracked per sender before the lowest-fee one is evicted
+    per_sender_limit: usize,
+    // max nonce distance ahead of the confirmed nonce a submission may take
+    nonce_cap: u64,
+    // rounds a tx can survive without inclusion before it's dropped
+    max_escalations: usize,
 }
 
 fn get_config() -> Config {
@@ -60,6 +96,49 @@ fn get_config() -> Config {
         port: env::var("PORT").map_or(3000, |s| {
             s.parse().expect("Missing or invalid \"PORT\" Env Var")
         }),
+        fee_history_block_window: env::var("FEE_HISTORY_BLOCK_WINDOW").map_or(20, |s| {
+            s.parse()
+                .expect("Invalid \"FEE_HISTORY_BLOCK_WINDOW\" Env Var")
+        }),
+        fee_history_percentile: env::var("FEE_HISTORY_PERCENTILE").map_or(50.0, |s| {
+            s.parse()
+                .expect("Invalid \"FEE_HISTORY_PERCENTILE\" Env Var")
+        }),
+        rpc_max_retries: env::var("RPC_MAX_RETRIES").map_or(10, |s| {
+            s.parse().expect("Invalid \"RPC_MAX_RETRIES\" Env Var")
+        }),
+        rpc_initial_backoff_ms: env::var("RPC_INITIAL_BACKOFF_MS").map_or(250, |s| {
+            s.parse()
+                .expect("Invalid \"RPC_INITIAL_BACKOFF_MS\" Env Var")
+        }),
+        wss_url: env::var("WSS_URL").ok(),
+        use_alchemy_ws: env::var("ALCHEMY_WS").map_or(false, |s| s == "true" || s == "1"),
+        ws_initial_backoff_ms: env::var("WS_INITIAL_BACKOFF_MS").map_or(250, |s| {
+            s.parse()
+                .expect("Invalid \"WS_INITIAL_BACKOFF_MS\" Env Var")
+        }),
+        ws_max_backoff_ms: env::var("WS_MAX_BACKOFF_MS").map_or(30_000, |s| {
+            s.parse()
+                .expect("Invalid \"WS_MAX_BACKOFF_MS\" Env Var")
+        }),
+        extra_rpc_urls: env::var("EXTRA_RPC_URLS").map_or(Vec::new(), |s| {
+            s.split(',')
+                .map(|url| url.trim().to_string())
+                .filter(|url| !url.is_empty())
+                .collect()
+        }),
+        rpc_quorum_threshold: env::var("RPC_QUORUM_THRESHOLD").map_or(1, |s| {
+            s.parse()
+                .expect("Invalid \"RPC_QUORUM_THRESHOLD\" Env Var")
+        }),
+        per_sender_limit: env::var("PER_SENDER_LIMIT").map_or(16, |s| {
+            s.parse().expect("Invalid \"PER_SENDER_LIMIT\" Env Var")
+        }),
+        nonce_cap: env::var("NONCE_CAP")
+            .map_or(16, |s| s.parse().expect("Invalid \"NONCE_CAP\" Env Var")),
+        max_escalations: env::var("MAX_ESCALATIONS").map_or(10, |s| {
+            s.parse().expect("Invalid \"MAX_ESCALATIONS\" Env Var")
+        }),
     }
 }
 
@@ -67,14 +146,39 @@ async fn setup_monitor(
     config: &Config,
     connection_pool: Pool<MySql>,
     chain: Chain,
-) -> ConfigedMonitor {
+) -> (ConfigedMonitor, TxKind) {
     let signer = LocalWallet::from_str(&config.pk_hex_string)
         .expect("Server not configured correct, invalid private key");
     let address = signer.address();
 
-    let rpc_url = get_rpc(chain, &config.alchemy_key);
-    let provider = Provider::<Http>::try_from(rpc_url)
-        .expect("Server not configured correctly, invalid provider url");
+    // fan every call out across the primary Alchemy endpoint plus any extras,
+    // so one stalled or lagging node can't wedge submission or inclusion
+    // checks; send_transaction goes out to all of them, reads are accepted
+    // once rpc_quorum_threshold of them agree
+    let rpc_urls = std::iter::once(get_rpc(chain, &config.alchemy_key))
+        .chain(config.extra_rpc_urls.iter().cloned());
+    let weighted_providers: Vec<WeightedProvider<RetryClient<Http>>> = rpc_urls
+        .map(|rpc_url| {
+            let rpc_url = rpc_url
+                .parse()
+                .expect("Server not configured correctly, invalid provider url");
+            let http = Http::new(rpc_url);
+            // back off (with jitter) and retry on 429s and transient JSON-RPC
+            // errors instead of the fixed sleep()s monitor() used to sprinkle
+            // between calls
+            let retry_client = RetryClientBuilder::new()
+                .rate_limit_retries(config.rpc_max_retries)
+                .timeout_retries(config.rpc_max_retries)
+                .initial_backoff(Duration::from_millis(config.rpc_initial_backoff_ms))
+                .build(http, Box::new(HttpRateLimitRetryPolicy));
+            WeightedProvider::new(retry_client)
+        })
+        .collect();
+    let quorum_provider = QuorumProviderBuilder::new()
+        .add_providers(weighted_providers)
+        .quorum(Quorum::ProviderCount(config.rpc_quorum_threshold))
+        .build();
+    let provider = Provider::new(quorum_provider);
     let provider = SignerMiddleware::new_with_provider_chain(provider, signer)
         .await
         .expect("Could not connect to provider");
@@ -84,7 +188,40 @@ async fn setup_monitor(
         .await
         .expect("Could not initialize nonce");
 
-    TransactionMonitor::new(provider, 3, connection_pool)
+    let block_source = match &config.wss_url {
+        Some(ws_url) => BlockSource::Subscription {
+            ws_url: ws_url.clone(),
+            initial_backoff: Duration::from_millis(config.ws_initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.ws_max_backoff_ms),
+        },
+        None if config.use_alchemy_ws => BlockSource::Subscription {
+            ws_url: get_ws_rpc(chain, &config.alchemy_key),
+            initial_backoff: Duration::from_millis(config.ws_initial_backoff_ms),
+            max_backoff: Duration::from_millis(config.ws_max_backoff_ms),
+        },
+        None => BlockSource::Polling,
+    };
+
+    let default_tx_kind = if supports_eip1559(chain) {
+        TxKind::Eip1559
+    } else {
+        TxKind::Legacy
+    };
+    info!(
+        "chain {:?} supports eip1559: {}, defaulting to {:?}",
+        chain,
+        supports_eip1559(chain),
+        default_tx_kind
+    );
+
+    let monitor = TransactionMonitor::new(provider, 3, connection_pool, block_source)
+        .await
+        .with_fee_history_oracle(config.fee_history_block_window, config.fee_history_percentile)
+        .await
+        .with_queue_limits(config.per_sender_limit, config.nonce_cap, config.max_escalations)
+        .await;
+
+    (monitor, default_tx_kind)
 }
 
 async fn simple_auth<B>(
@@ -120,12 +257,14 @@ async fn main() {
         .connect(&config.database_url)
         .await
         .expect("Could not connect to database");
-    let monitor: ConfigedMonitor = setup_monitor(&config, connection_pool, Chain::Sepolia).await;
+    let (monitor, default_tx_kind): (ConfigedMonitor, TxKind) =
+        setup_monitor(&config, connection_pool, Chain::Sepolia).await;
 
     let port = config.port;
     let shared_state = AppState {
         monitor: Arc::new(monitor),
         config: Arc::new(config),
+        default_tx_kind,
     };
 
     let app = Router::new()
@@ -147,14 +286,41 @@ async fn relay_transaction(
     State(state): State<Arc<AppState>>,
     Json(payload): Json<RelayRequest>,
 ) -> Result<String, ServerError> {
-    let mut request = Eip1559TransactionRequest::new()
-        .to(payload.to)
-        .value(payload.value)
-        .max_priority_fee_per_gas(1);
-    request.data = payload.data.map(|data| data.into());
+    let data = payload.data.map(|data| data.into());
+    let kind = payload.kind.unwrap_or(state.default_tx_kind);
+
+    let request: TypedTransaction = match kind {
+        TxKind::Eip1559 => {
+            let mut tx = Eip1559TransactionRequest::new()
+                .to(payload.to)
+                .value(payload.value)
+                .max_priority_fee_per_gas(1);
+            tx.data = data;
+            if let Some(access_list) = payload.access_list {
+                tx = tx.access_list(access_list);
+            }
+            tx.into()
+        }
+        TxKind::Legacy => {
+            let mut tx = TransactionRequest::new().to(payload.to).value(payload.value);
+            tx.data = data;
+            match payload.access_list {
+                Some(access_list) => Eip2930TransactionRequest::new(tx, access_list).into(),
+                None => tx.into(),
+            }
+        }
+    };
 
     info!("Transaction: {:?}", request);
-    let id = state.monitor.send_monitored_transaction(request).await?;
+    let id = state
+        .monitor
+        .send_monitored_transaction_with_options(
+            request,
+            None,
+            None,
+            payload.speed.unwrap_or(Speed::Standard),
+        )
+        .await?;
 
     Ok(id.to_string())
 }
@@ -196,6 +362,16 @@ struct RelayRequest {
     #[serde(default)]
     #[serde(deserialize_with = "hex_opt")]
     data: Option<Vec<u8>>,
+    #[serde(default)]
+    speed: Option<Speed>,
+    // envelope to submit with; defaults to whatever the configured chain
+    // supports (see `AppState::default_tx_kind`)
+    #[serde(default)]
+    kind: Option<TxKind>,
+    // attaches an access list to the submitted tx; a legacy `kind` becomes an
+    // EIP-2930 transaction, while an eip1559 `kind` stays EIP-1559
+    #[serde(default)]
+    access_list: Option<AccessList>,
 }
 
 impl fmt::Debug for RelayRequest {
@@ -203,6 +379,8 @@ impl fmt::Debug for RelayRequest {
         f.debug_struct("Relay Request")
             .field("to", &self.to)
             .field("data", &self.data) // TODO add value here
+            .field("speed", &self.speed)
+            .field("kind", &self.kind)
             .finish()
     }
 }
@@ -0,0 +1,22 @@
+use std::time::Duration;
+
+/// Where `monitor` gets its "a new block landed" signal from. Fixed for the
+/// lifetime of a `TransactionMonitor`, since `monitor` is spawned once in
+/// `new` and switching transports mid-flight isn't a supported operation.
+#[derive(Debug, Clone)]
+pub enum BlockSource {
+    /// Filter-polls the primary provider via `eth_newBlockFilter` +
+    /// `eth_getFilterChanges` (`Middleware::watch_blocks`). Works over any
+    /// transport, at the cost of polling latency and extra request quota.
+    Polling,
+    /// Pushes new heads over a websocket subscription (`eth_subscribe`) to
+    /// `ws_url`, reconnecting with exponential backoff (starting at
+    /// `initial_backoff`, capped at `max_backoff`) if the socket drops. Falls
+    /// back to polling the primary provider if `ws_url` can't be connected to
+    /// at all.
+    Subscription {
+        ws_url: String,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+    },
+}
@@ -0,0 +1,103 @@
+use ethers::types::U256;
+
+/// A pluggable strategy for computing the fee (tip or base fee) to retry a
+/// stuck transaction with. Takes the fee the transaction was originally
+/// submitted with and the number of escalation rounds it has already been
+/// through, and returns the fee to use for this round.
+pub type EscalationPolicy = Box<dyn Fn(U256, usize) -> U256 + Send + Sync>;
+
+/// Bumps the fee by a fixed absolute amount per escalation round:
+/// `original + step * n`.
+pub fn linear(step: U256) -> EscalationPolicy {
+    Box::new(move |original, n| original + step * n)
+}
+
+/// Bumps the fee by a fixed percentage per escalation round, compounding:
+/// `original * (numerator / denominator) ^ n`.
+pub fn geometric(numerator: u64, denominator: u64) -> EscalationPolicy {
+    Box::new(move |original, n| {
+        let mut fee = original;
+        for _ in 0..n {
+            fee = fee * numerator / denominator;
+        }
+        fee
+    })
+}
+
+/// The original behavior: bump by at least 10% (plus 1 for rounding) each
+/// escalation round, matching the minimum replacement bump most nodes
+/// enforce.
+/// https://github.com/ethereum/go-ethereum/issues/23616#issuecomment-924657965
+pub fn min_10_percent() -> EscalationPolicy {
+    Box::new(|original, n| {
+        let mut fee = original;
+        for _ in 0..n {
+            let increase = (fee * 10) / 100u64;
+            fee = fee + increase + 1;
+        }
+        fee
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_is_unchanged_at_round_zero() {
+        let policy = linear(U256::from(100));
+        assert_eq!(policy(U256::from(1_000), 0), U256::from(1_000));
+    }
+
+    #[test]
+    fn linear_bumps_by_a_fixed_step_per_round() {
+        let policy = linear(U256::from(100));
+        assert_eq!(policy(U256::from(1_000), 1), U256::from(1_100));
+        assert_eq!(policy(U256::from(1_000), 3), U256::from(1_300));
+    }
+
+    #[test]
+    fn geometric_is_unchanged_at_round_zero() {
+        let policy = geometric(110, 100);
+        assert_eq!(policy(U256::from(1_000), 0), U256::from(1_000));
+    }
+
+    #[test]
+    fn geometric_compounds_round_over_round() {
+        let policy = geometric(110, 100);
+        // each round multiplies the *previous* round's fee by 110/100, not the
+        // original, so two rounds should outpace a single doubled round
+        let one_round = policy(U256::from(1_000), 1);
+        let two_rounds = policy(U256::from(1_000), 2);
+        assert_eq!(one_round, U256::from(1_100));
+        assert_eq!(two_rounds, U256::from(1_210));
+    }
+
+    #[test]
+    fn min_10_percent_is_unchanged_at_round_zero() {
+        let policy = min_10_percent();
+        assert_eq!(policy(U256::from(1_000), 0), U256::from(1_000));
+    }
+
+    #[test]
+    fn min_10_percent_is_monotonically_increasing() {
+        let policy = min_10_percent();
+        let mut prev = policy(U256::from(1_000), 0);
+        for n in 1..=5 {
+            let next = policy(U256::from(1_000), n);
+            assert!(next > prev);
+            prev = next;
+        }
+    }
+
+    #[test]
+    fn min_10_percent_clears_at_least_a_ten_percent_bump_each_round() {
+        let policy = min_10_percent();
+        for n in 1..=5 {
+            let fee_before = policy(U256::from(1_000), n - 1);
+            let fee_after = policy(U256::from(1_000), n);
+            let min_expected = fee_before + fee_before * 10u64 / 100u64;
+            assert!(fee_after >= min_expected);
+        }
+    }
+}
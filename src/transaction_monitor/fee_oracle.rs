@@ -0,0 +1,74 @@
+use ethers::{
+    providers::Middleware,
+    types::{BlockNumber, U256},
+};
+use serde::{Deserialize, Serialize};
+
+/// How urgently a transaction should land, mapped to the reward percentile
+/// requested from `eth_feeHistory`. Higher percentiles reflect what
+/// transactions near the front of recent blocks actually paid.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Speed {
+    Slow,
+    Standard,
+    Fast,
+}
+
+impl Speed {
+    const SLOW_PERCENTILE: f64 = 25.0;
+    const FAST_PERCENTILE: f64 = 90.0;
+
+    /// `standard_percentile` is the operator-configured target percentile
+    /// used for the "standard" tier; slow/fast always use the fixed 25th/90th
+    /// percentiles and ignore it.
+    pub fn percentile(&self, standard_percentile: f64) -> f64 {
+        match self {
+            Speed::Slow => Self::SLOW_PERCENTILE,
+            Speed::Standard => standard_percentile,
+            Speed::Fast => Self::FAST_PERCENTILE,
+        }
+    }
+}
+
+/// Suggests EIP-1559 fees from recent fee history rather than the node's
+/// single-point estimate, so callers pay closer to what the last
+/// `block_window` blocks actually required for inclusion at `percentile`.
+/// Returns `(max_fee_per_gas, max_priority_fee_per_gas)`.
+pub async fn estimate_fees_from_history<M: Middleware>(
+    provider: &M,
+    block_window: u64,
+    percentile: f64,
+) -> Result<(U256, U256), M::Error> {
+    let history = provider
+        .fee_history(block_window, BlockNumber::Latest, &[percentile])
+        .await?;
+
+    let mut rewards: Vec<U256> = history
+        .reward
+        .iter()
+        .filter_map(|block_rewards| block_rewards.first().copied())
+        .collect();
+    rewards.sort();
+
+    let priority_fee = if rewards.is_empty() {
+        U256::zero()
+    } else {
+        rewards[rewards.len() / 2]
+    };
+
+    let latest_base_fee = *history.base_fee_per_gas.last().unwrap_or(&U256::zero());
+    // double the latest base fee so the max fee still clears a couple of
+    // blocks of further base fee increases, same headroom ethers' own
+    // estimate_eip1559_fees leaves by default
+    let max_fee = latest_base_fee * 2 + priority_fee;
+
+    Ok((max_fee, priority_fee))
+}
+
+/// Suggests a flat `gas_price` for legacy/EIP-2930 transactions via
+/// `eth_gasPrice`, since chains without an EIP-1559 fee market often don't
+/// implement (or meaningfully support) `eth_feeHistory`.
+pub async fn estimate_gas_price<M: Middleware>(provider: &M) -> Result<U256, M::Error> {
+    provider.get_gas_price().await
+}
@@ -1,33 +1,103 @@
 use anyhow::Context;
 use ethers::{
-    providers::{Middleware, StreamExt},
-    types::{
-        transaction::eip2718::TypedTransaction, BlockId, Eip1559TransactionRequest, TxHash, H256,
-        U256,
-    },
+    providers::{Middleware, Provider, StreamExt, Ws},
+    types::{transaction::eip2718::TypedTransaction, BlockId, TxHash, H256, U256},
 };
 use futures_util::lock::Mutex;
-use std::{cmp::max, pin::Pin, sync::Arc};
-use tracing::{info, trace};
+use sqlx::{MySql, Pool};
+use std::{
+    cmp::{max, min},
+    pin::Pin,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{spawn, time::sleep};
+use tracing::{info, trace, warn};
 use uuid::Uuid;
 
-use tokio::{
-    spawn,
-    time::{sleep, Duration},
-};
+mod escalation_policy;
+pub use escalation_policy::{geometric, linear, min_10_percent, EscalationPolicy};
+
+mod fee_oracle;
+pub use fee_oracle::Speed;
+use fee_oracle::{estimate_fees_from_history, estimate_gas_price};
+
+mod pending_queue;
+use pending_queue::PendingQueue;
+
+mod block_source;
+pub use block_source::BlockSource;
+
+mod transaction_kind;
+pub use transaction_kind::{supports_eip1559, TxKind};
+use transaction_kind::OriginalFees;
+
+mod store;
+
+/// Default number of past blocks to sample with `eth_feeHistory`.
+const DEFAULT_FEE_HISTORY_BLOCK_WINDOW: u64 = 20;
+/// Default reward percentile used for the "standard" speed tier.
+const DEFAULT_FEE_HISTORY_PERCENTILE: f64 = 50.0;
+/// Default cap on how many pending txs a single sender may have queued.
+const DEFAULT_PER_SENDER_LIMIT: usize = 16;
+/// Default cap on how far ahead of the confirmed nonce a submission may be.
+const DEFAULT_NONCE_CAP: u64 = 16;
+/// Default number of escalation rounds a tx can survive before being dropped.
+const DEFAULT_MAX_ESCALATIONS: usize = 10;
+
 type WatcherFuture<'a> = Pin<Box<dyn futures_util::stream::Stream<Item = H256> + Send + 'a>>;
 
+/// A transaction we're tracking for inclusion, along with everything needed
+/// to escalate it if it stalls.
 #[derive(Debug)]
-pub enum Status {
-    Pending,
-    Complete,
+struct PendingTx {
+    tx_hash: TxHash,
+    request: TypedTransaction,
+    priority: Option<BlockId>,
+    id: Uuid,
+    escalations: usize,
+    original_fees: OriginalFees,
+    // Per-transaction override of the monitor's default escalation policy.
+    policy_override: Option<Arc<EscalationPolicy>>,
+    speed: Speed,
+}
+
+/// Runtime-tunable settings, grouped behind one lock so a builder call made
+/// after `new()` (which has already spawned the background `monitor` task
+/// off a clone of `self`) is observed by that task too.
+struct MonitorConfig {
+    escalation_policy: Arc<EscalationPolicy>,
+    fee_history_block_window: u64,
+    fee_history_percentile: f64,
+    per_sender_limit: usize,
+    nonce_cap: u64,
+    max_escalations: usize,
+}
+
+impl Default for MonitorConfig {
+    fn default() -> Self {
+        Self {
+            escalation_policy: Arc::new(min_10_percent()),
+            fee_history_block_window: DEFAULT_FEE_HISTORY_BLOCK_WINDOW,
+            fee_history_percentile: DEFAULT_FEE_HISTORY_PERCENTILE,
+            per_sender_limit: DEFAULT_PER_SENDER_LIMIT,
+            nonce_cap: DEFAULT_NONCE_CAP,
+            max_escalations: DEFAULT_MAX_ESCALATIONS,
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct TransactionMonitor<M> {
     pub provider: Arc<M>,
-    pub txs: Arc<Mutex<Vec<(TxHash, Eip1559TransactionRequest, Option<BlockId>, Uuid)>>>, // Is the mutex really necessary here, we're only gonna have two tasks sharing this
+    pub txs: Arc<Mutex<PendingQueue>>, // per-sender ready/future partitioned pending-tx pool
     pub block_frequency: u8,
+    config: Arc<Mutex<MonitorConfig>>,
+    store: Pool<MySql>,
+    // Fixed at construction: `monitor` is spawned once in `new`, so unlike
+    // the settings above there's no live clone left around to observe a
+    // later change.
+    block_source: BlockSource,
 }
 
 impl<M> Clone for TransactionMonitor<M> {
@@ -36,6 +106,9 @@ impl<M> Clone for TransactionMonitor<M> {
             provider: self.provider.clone(),
             txs: self.txs.clone(),
             block_frequency: self.block_frequency.clone(),
+            config: self.config.clone(),
+            store: self.store.clone(),
+            block_source: self.block_source.clone(),
         }
     }
 }
@@ -44,11 +117,51 @@ impl<M> TransactionMonitor<M>
 where
     M: Middleware + 'static,
 {
-    pub fn new(provider: M, block_frequency: u8) -> Self {
+    /// Ensures the backing table exists, reloads any non-terminal txs from a
+    /// previous run, and starts the background escalation loop, taking its
+    /// new-block signal from `block_source`.
+    pub async fn new(
+        provider: M,
+        block_frequency: u8,
+        store: Pool<MySql>,
+        block_source: BlockSource,
+    ) -> Self {
+        store::ensure_schema(&store)
+            .await
+            .expect("could not set up monitored_transactions table");
+
+        let mut txs = PendingQueue::new();
+        let recovered = store::load_pending(&store)
+            .await
+            .expect("could not reload pending transactions");
+        for stored in recovered {
+            let sender = stored.request.from().copied().unwrap_or_default();
+            let Some(nonce) = stored.request.nonce().copied() else {
+                continue;
+            };
+            txs.restore(
+                sender,
+                nonce,
+                PendingTx {
+                    tx_hash: stored.tx_hash,
+                    original_fees: stored.original_fees,
+                    request: stored.request,
+                    priority: None,
+                    id: stored.id,
+                    escalations: stored.escalations,
+                    policy_override: None,
+                    speed: stored.speed,
+                },
+            );
+        }
+
         let this = Self {
             provider: Arc::new(provider),
-            txs: Arc::new(Mutex::new(Vec::new())),
+            txs: Arc::new(Mutex::new(txs)),
             block_frequency,
+            config: Arc::new(Mutex::new(MonitorConfig::default())),
+            store,
+            block_source,
         };
 
         {
@@ -61,56 +174,220 @@ where
         this
     }
 
+    /// Sets the escalation policy used for every monitored transaction that
+    /// doesn't specify its own via `send_monitored_transaction_with_policy`.
+    pub async fn with_escalation_policy(self, policy: EscalationPolicy) -> Self {
+        self.config.lock().await.escalation_policy = Arc::new(policy);
+        self
+    }
+
+    /// Configures the `eth_feeHistory` window and the reward percentile used
+    /// for the "standard" speed tier (see `Speed::percentile`).
+    pub async fn with_fee_history_oracle(self, block_window: u64, standard_percentile: f64) -> Self {
+        let mut config = self.config.lock().await;
+        config.fee_history_block_window = block_window;
+        config.fee_history_percentile = standard_percentile;
+        drop(config);
+        self
+    }
+
+    /// Configures the pending-queue bounds: at most `per_sender_limit` txs
+    /// tracked per sender, submissions rejected if their nonce is more than
+    /// `nonce_cap` ahead of the confirmed nonce, and a tx dropped once it has
+    /// survived `max_escalations` rounds without being included.
+    pub async fn with_queue_limits(
+        self,
+        per_sender_limit: usize,
+        nonce_cap: u64,
+        max_escalations: usize,
+    ) -> Self {
+        let mut config = self.config.lock().await;
+        config.per_sender_limit = per_sender_limit;
+        config.nonce_cap = nonce_cap;
+        config.max_escalations = max_escalations;
+        drop(config);
+        self
+    }
+
     pub async fn send_monitored_transaction(
         &self,
-        tx: Eip1559TransactionRequest,
+        tx: TypedTransaction,
         block: Option<BlockId>,
     ) -> Result<Uuid, anyhow::Error> {
-        let mut with_gas = tx.clone();
-        if with_gas.max_fee_per_gas.is_none() || with_gas.max_priority_fee_per_gas.is_none() {
-            let (estimate_max_fee, estimate_max_priority_fee) = self
-                .provider
-                .estimate_eip1559_fees(None)
-                .await
-                .with_context(|| "error estimating gas")?;
-            with_gas.max_fee_per_gas = Some(estimate_max_fee);
-            with_gas.max_priority_fee_per_gas = Some(estimate_max_priority_fee);
+        self.send_monitored_transaction_with_options(tx, block, None, Speed::Standard)
+            .await
+    }
+
+    /// Same as `send_monitored_transaction`, but escalates this specific
+    /// transaction using `policy` instead of the monitor's default.
+    pub async fn send_monitored_transaction_with_policy(
+        &self,
+        tx: TypedTransaction,
+        block: Option<BlockId>,
+        policy: Option<EscalationPolicy>,
+    ) -> Result<Uuid, anyhow::Error> {
+        self.send_monitored_transaction_with_options(tx, block, policy, Speed::Standard)
+            .await
+    }
+
+    /// Same as `send_monitored_transaction`, but sources the fee estimate
+    /// from the `speed` tier's `eth_feeHistory` percentile instead of the
+    /// standard one, and keeps escalating at that same tier. `tx` may be a
+    /// legacy, EIP-2930 or EIP-1559 request; whichever gas fields it's
+    /// missing are filled from the fee oracle appropriate to its variant.
+    pub async fn send_monitored_transaction_with_options(
+        &self,
+        mut tx: TypedTransaction,
+        block: Option<BlockId>,
+        policy: Option<EscalationPolicy>,
+        speed: Speed,
+    ) -> Result<Uuid, anyhow::Error> {
+        if tx.gas_price().is_none() {
+            let legacy = matches!(tx, TypedTransaction::Legacy(_) | TypedTransaction::Eip2930(_));
+            let (estimate_max_fee, estimate_max_priority_fee) =
+                self.estimate_fees(speed, legacy).await?;
+            if legacy {
+                transaction_kind::set_gas_price(&mut tx, estimate_max_fee);
+            } else {
+                transaction_kind::set_eip1559_fees(
+                    &mut tx,
+                    estimate_max_fee,
+                    estimate_max_priority_fee,
+                );
+            }
         }
-        let mut filled: TypedTransaction = with_gas.clone().into();
+
         self.provider
-            .fill_transaction(&mut filled, None)
+            .fill_transaction(&mut tx, None)
             .await
             .with_context(|| "error while filling transaction")?;
 
-        info!("Filled Transaction {:?}", filled);
+        info!("Filled Transaction {:?}", tx);
 
         let pending_tx = self
             .provider
-            .send_transaction(filled.clone(), block)
+            .send_transaction(tx.clone(), block)
             .await
             .with_context(|| "error sending transaction")?;
 
         let id = Uuid::new_v4();
+        let sender = tx.from().copied().unwrap_or_default();
+        let nonce = *tx
+            .nonce()
+            .with_context(|| "filled transaction has no nonce")?;
+        let original_fees = OriginalFees::from_typed_transaction(&tx);
+
+        let entry = PendingTx {
+            tx_hash: *pending_tx,
+            request: tx,
+            priority: block,
+            id,
+            escalations: 0,
+            original_fees,
+            policy_override: policy.map(Arc::new),
+            speed,
+        };
 
-        // insert the tx in the pending txs
-        let mut lock = self.txs.lock().await;
-        lock.push((*pending_tx, filled.clone().into(), block, id));
+        let (per_sender_limit, nonce_cap) = {
+            let config = self.config.lock().await;
+            (config.per_sender_limit, config.nonce_cap)
+        };
+
+        store::upsert(
+            &self.store,
+            id,
+            *pending_tx,
+            &entry.request,
+            &entry.original_fees,
+            entry.speed,
+            0,
+        )
+        .await
+        .with_context(|| "error persisting transaction")?;
+
+        let evicted = self
+            .txs
+            .lock()
+            .await
+            .insert(sender, nonce, entry, per_sender_limit, nonce_cap)
+            .with_context(|| "error queueing transaction")?;
+        if let Some(evicted) = evicted {
+            info!(
+                "evicted lowest-fee transaction {:?} from {:?}'s queue to make room",
+                evicted.id, sender
+            );
+            store::mark_dropped(&self.store, evicted.id)
+                .await
+                .with_context(|| "error persisting evicted transaction")?;
+        }
 
         Ok(id)
     }
 
-    // TODO improve this XD
-    pub async fn get_transaction_status(&self, id: Uuid) -> Status {
-        let lock = self.txs.lock().await;
-        info!("here's the current txs {:?}", lock);
-        match lock.iter().find(|(_, _, _, entry_id)| id == *entry_id) {
-            None => Status::Complete,
-            Some(_) => Status::Pending,
+    /// Looks up the suggested fees for `speed`: for a legacy/EIP-2930 tx,
+    /// `(gas_price, 0)` from `eth_gasPrice`; for an EIP-1559 tx,
+    /// `(max_fee_per_gas, max_priority_fee_per_gas)` from recent fee history.
+    async fn estimate_fees(&self, speed: Speed, legacy: bool) -> Result<(U256, U256), anyhow::Error> {
+        if legacy {
+            let gas_price = estimate_gas_price(self.provider.as_ref())
+                .await
+                .with_context(|| "error estimating gas price")?;
+            return Ok((gas_price, U256::zero()));
+        }
+
+        let (block_window, percentile) = {
+            let config = self.config.lock().await;
+            (
+                config.fee_history_block_window,
+                speed.percentile(config.fee_history_percentile),
+            )
+        };
+
+        estimate_fees_from_history(self.provider.as_ref(), block_window, percentile)
+            .await
+            .with_context(|| "error estimating gas from fee history")
+    }
+
+    /// Returns `(mined, hash)` for `id`, or `None` if we've never heard of
+    /// it. A transaction still being escalated reads through the in-memory
+    /// queue; anything else reads through to the durable store, so a
+    /// completed (or dropped) tx stays distinguishable from an unknown id
+    /// across restarts and after it's evicted from memory.
+    pub async fn get_transaction_status(
+        &self,
+        id: Uuid,
+    ) -> Result<Option<(bool, String)>, anyhow::Error> {
+        if let Some(entry) = self.txs.lock().await.find(id) {
+            return Ok(Some((false, format!("{:?}", entry.tx_hash))));
         }
+
+        store::get_terminal_status(&self.store, id)
+            .await
+            .with_context(|| "error reading transaction status")
     }
 
+    /// Runs the background escalation loop forever, reacting to new blocks
+    /// from whichever source `block_source` names.
     pub async fn monitor(&self) -> Result<(), anyhow::Error> {
         info!("Monitoring for escalation!");
+        let mut block_count: u8 = 0;
+
+        match &self.block_source {
+            BlockSource::Polling => self.monitor_via_polling(&mut block_count).await,
+            BlockSource::Subscription {
+                ws_url,
+                initial_backoff,
+                max_backoff,
+            } => {
+                self.monitor_via_subscription(ws_url, *initial_backoff, *max_backoff, &mut block_count)
+                    .await
+            }
+        }
+    }
+
+    /// Filter-polls the primary provider for new blocks via `watch_blocks`.
+    /// Works over any transport, including plain HTTP.
+    async fn monitor_via_polling(&self, block_count: &mut u8) -> Result<(), anyhow::Error> {
         let mut watcher: WatcherFuture = Box::pin(
             self.provider
                 .watch_blocks()
@@ -118,71 +395,204 @@ where
                 .with_context(|| "Block streaming failure")?
                 .map(|hash| (hash)),
         );
-        let mut block_count = 0;
 
         while let Some(block_hash) = watcher.next().await {
-            // We know the block exists at this point
             info!("Block {:?} has been mined", block_hash);
-            block_count = block_count + 1;
+            *block_count += 1;
+            self.process_ready_transactions(*block_count).await?;
+        }
 
-            let block = self
-                .provider
-                .get_block_with_txs(block_hash)
-                .await
-                .with_context(|| "error while fetching block")?
-                .unwrap();
-            sleep(Duration::from_secs(1)).await; // to avoid rate limiting
+        Ok(())
+    }
 
-            let (estimate_max_fee, estimate_max_priority_fee) = self
-                .provider
-                .estimate_eip1559_fees(None)
-                .await
-                .with_context(|| "error estimating gas prices")?;
+    /// Subscribes to new heads over a websocket connection to `ws_url`,
+    /// reconnecting with exponential backoff (capped at `max_backoff`)
+    /// whenever the socket drops or the subscription can't be established. A
+    /// round of escalation that errors out is logged and retried on the next
+    /// block over the same subscription. Falls back to `monitor_via_polling`
+    /// once `MAX_SUBSCRIBE_FAILURES` consecutive connect/subscribe attempts
+    /// fail.
+    async fn monitor_via_subscription(
+        &self,
+        ws_url: &str,
+        initial_backoff: Duration,
+        max_backoff: Duration,
+        block_count: &mut u8,
+    ) -> Result<(), anyhow::Error> {
+        const MAX_SUBSCRIBE_FAILURES: u32 = 5;
+
+        let mut backoff = initial_backoff;
+        let mut consecutive_failures: u32 = 0;
+
+        loop {
+            if consecutive_failures >= MAX_SUBSCRIBE_FAILURES {
+                warn!(
+                    "websocket endpoint {:?} failed {} times in a row; falling back to polling",
+                    ws_url, consecutive_failures
+                );
+                return self.monitor_via_polling(block_count).await;
+            }
 
-            let mut txs = self.txs.lock().await;
-            let len = txs.len();
+            let ws_provider = match Provider::<Ws>::connect(ws_url).await {
+                Ok(ws_provider) => ws_provider,
+                Err(err) => {
+                    consecutive_failures += 1;
+                    warn!(
+                        "could not connect to websocket endpoint {:?} ({:?}); retrying in {:?}",
+                        ws_url, err, backoff
+                    );
+                    sleep(backoff).await;
+                    backoff = min(backoff * 2, max_backoff);
+                    continue;
+                }
+            };
+
+            let subscription = match ws_provider.subscribe_blocks().await {
+                Ok(subscription) => subscription,
+                Err(err) => {
+                    consecutive_failures += 1;
+                    warn!(
+                        "could not subscribe to new heads on {:?} ({:?}); retrying in {:?}",
+                        ws_url, err, backoff
+                    );
+                    sleep(backoff).await;
+                    backoff = min(backoff * 2, max_backoff);
+                    continue;
+                }
+            };
+
+            consecutive_failures = 0;
+            backoff = initial_backoff;
+
+            let mut stream: Pin<Box<dyn futures_util::stream::Stream<Item = H256> + Send + '_>> =
+                Box::pin(subscription.map(|block| block.hash.unwrap_or_default()));
+
+            loop {
+                match stream.next().await {
+                    Some(block_hash) => {
+                        info!("Block {:?} has been mined", block_hash);
+                        *block_count += 1;
+                        if let Err(err) = self.process_ready_transactions(*block_count).await {
+                            warn!(
+                                "error processing transactions for block {:?}, will retry next block: {:?}",
+                                block_hash, err
+                            );
+                        }
+                    }
+                    None => break,
+                }
+            }
 
-            for _ in 0..len {
-                // this must never panic as we're explicitly within bounds
-                let (tx_hash, mut replacement_tx, priority, id) =
-                    txs.pop().expect("should have element in vector");
+            warn!(
+                "websocket subscription to {:?} dropped, reconnecting in {:?}",
+                ws_url, backoff
+            );
+            sleep(backoff).await;
+            backoff = min(backoff * 2, max_backoff);
+        }
+    }
 
-                let tx_has_been_included = block
-                    .transactions
-                    .iter()
-                    .find(|tx| tx.hash == tx_hash)
-                    .is_some();
-                info!("checking if transaction {:?} was included", tx_hash);
+    /// One round of escalation: refreshes confirmed nonces (marking anything
+    /// they've superseded as complete), then bumps or drops everything that's
+    /// ready and due for a replacement this round.
+    async fn process_ready_transactions(&self, block_count: u8) -> Result<(), anyhow::Error> {
+        let senders = self.txs.lock().await.senders_with_pending_txs();
+        for sender in senders {
+            let confirmed_nonce = self
+                .provider
+                .get_transaction_count(sender, None)
+                .await
+                .with_context(|| "error fetching confirmed nonce")?;
+            let included = self
+                .txs
+                .lock()
+                .await
+                .set_confirmed_nonce(sender, confirmed_nonce);
+            for entry in included {
+                info!("transaction {:?} has been included", entry.tx_hash);
+                store::mark_complete(&self.store, entry.id)
+                    .await
+                    .with_context(|| "error persisting included transaction")?;
+            }
+        }
 
-                if tx_has_been_included {
-                    info!("transaction {:?} was included", tx_hash);
-                    continue;
+        let ready = self.txs.lock().await.drain_ready();
+
+        // cache fee estimates per (speed, is_legacy) pair since several
+        // pending txs can share one, and each lookup is its own RPC call
+        let mut fee_estimates_by_speed: Vec<(Speed, bool, (U256, U256))> = Vec::new();
+        let max_escalations = self.config.lock().await.max_escalations;
+
+        for (sender, nonce, mut entry) in ready {
+            if block_count % self.block_frequency != 0 {
+                info!(
+                    "transaction {:?} was not included, not sending replacement yet",
+                    entry.tx_hash
+                );
+                self.txs
+                    .lock()
+                    .await
+                    .requeue_or_expire(sender, nonce, entry, max_escalations);
+                continue;
+            }
+
+            let legacy = matches!(entry.original_fees, OriginalFees::Legacy { .. });
+            let (estimate_max_fee, estimate_max_priority_fee) = match fee_estimates_by_speed
+                .iter()
+                .find(|(speed, is_legacy, _)| *speed == entry.speed && *is_legacy == legacy)
+            {
+                Some((_, _, estimate)) => *estimate,
+                None => {
+                    let estimate = self.estimate_fees(entry.speed, legacy).await?;
+                    fee_estimates_by_speed.push((entry.speed, legacy, estimate));
+                    estimate
                 }
+            };
 
-                if block_count % self.block_frequency != 0 {
+            match self
+                .rebroadcast(&mut entry, estimate_max_fee, estimate_max_priority_fee)
+                .await?
+            {
+                Some(new_txhash) => {
                     info!(
-                        "transaction {:?} was not included, not sending replacement yet",
-                        tx_hash
+                        "Transaction {:?} replaced with {:?}",
+                        entry.tx_hash, new_txhash
                     );
-                    txs.push((tx_hash, replacement_tx, priority, id));
-                    continue;
-                }
-
-                match self
-                    .rebroadcast(
-                        &mut replacement_tx,
-                        estimate_max_fee,
-                        estimate_max_priority_fee,
-                        priority,
+                    entry.tx_hash = new_txhash;
+
+                    store::upsert(
+                        &self.store,
+                        entry.id,
+                        new_txhash,
+                        &entry.request,
+                        &entry.original_fees,
+                        entry.speed,
+                        entry.escalations,
                     )
-                    .await?
-                {
-                    Some(new_txhash) => {
-                        info!("Transaction {:?} replaced with {:?}", tx_hash, new_txhash);
-                        txs.push((new_txhash, replacement_tx, priority, id));
-                        sleep(Duration::from_secs(1)).await; // to avoid rate limiting TODO add retries
+                    .await
+                    .with_context(|| "error persisting rebroadcast transaction")?;
+
+                    let id = entry.id;
+                    if let Some(dropped) = self
+                        .txs
+                        .lock()
+                        .await
+                        .requeue_or_expire(sender, nonce, entry, max_escalations)
+                    {
+                        info!(
+                            "transaction {:?} dropped after {} escalations without inclusion",
+                            id, dropped.escalations
+                        );
+                        store::mark_dropped(&self.store, id)
+                            .await
+                            .with_context(|| "error persisting dropped transaction")?;
                     }
-                    None => {}
+                }
+                None => {
+                    info!("transaction has already been included");
+                    store::mark_complete(&self.store, entry.id)
+                        .await
+                        .with_context(|| "error persisting included transaction")?;
                 }
             }
         }
@@ -192,14 +602,18 @@ where
 
     async fn rebroadcast(
         &self,
-        tx: &mut Eip1559TransactionRequest,
+        entry: &mut PendingTx,
         estimate_max_fee: U256,
         estimate_max_priority_fee: U256,
-        priority: Option<BlockId>,
     ) -> Result<Option<H256>, anyhow::Error> {
-        self.bump_transaction(tx, estimate_max_fee, estimate_max_priority_fee);
+        self.bump_transaction(entry, estimate_max_fee, estimate_max_priority_fee)
+            .await;
 
-        match self.provider.send_transaction(tx.clone(), priority).await {
+        match self
+            .provider
+            .send_transaction(entry.request.clone(), entry.priority)
+            .await
+        {
             Ok(new_tx_hash) => {
                 return Ok(Some(*new_tx_hash));
             }
@@ -219,30 +633,86 @@ where
         };
     }
 
-    fn bump_transaction(
+    async fn bump_transaction(
         &self,
-        tx: &mut Eip1559TransactionRequest,
+        entry: &mut PendingTx,
         estimate_max_fee: U256,
         estimate_max_priority_fee: U256,
     ) {
-        // We should never risk getting gas too low errors because we set these vals in send_monitored_transaction
-        let prev_max_priority_fee = tx
-            .max_priority_fee_per_gas
-            .unwrap_or(estimate_max_priority_fee);
-        let prev_max_fee = tx.max_fee_per_gas.unwrap_or(estimate_max_fee);
-
-        let new_max_priority_fee = max(
-            estimate_max_priority_fee,
-            self.increase_by_minimum(prev_max_priority_fee),
-        );
-
-        let estimate_base_fee = estimate_max_fee - estimate_max_priority_fee;
-        let prev_base_fee = prev_max_fee - prev_max_priority_fee;
-        let new_base_fee = max(estimate_base_fee, self.increase_by_minimum(prev_base_fee));
-        let new_max_fee = new_base_fee + new_max_priority_fee;
-
-        tx.max_fee_per_gas = Some(new_max_fee);
-        tx.max_priority_fee_per_gas = Some(new_max_priority_fee);
+        entry.escalations += 1;
+        let escalation_policy = self.config.lock().await.escalation_policy.clone();
+        let policy = entry
+            .policy_override
+            .as_deref()
+            .unwrap_or(escalation_policy.as_ref());
+
+        match entry.original_fees {
+            OriginalFees::Legacy {
+                gas_price: original_gas_price,
+            } => {
+                // We should never risk a gas-too-low error because we set
+                // this in send_monitored_transaction
+                let prev_gas_price = entry.request.gas_price().unwrap_or(estimate_max_fee);
+
+                // the fee the policy proposes for this round, computed from
+                // the fee the transaction was originally submitted with
+                let policy_gas_price = policy(original_gas_price, entry.escalations);
+
+                // never go below the latest network estimate, and always
+                // clear the minimum 10% bump over the last fee we actually
+                // sent, regardless of what the policy proposes
+                let new_gas_price = max(
+                    estimate_max_fee,
+                    max(policy_gas_price, self.increase_by_minimum(prev_gas_price)),
+                );
+
+                transaction_kind::set_gas_price(&mut entry.request, new_gas_price);
+            }
+            OriginalFees::Eip1559 {
+                max_fee: original_max_fee,
+                priority_fee: original_priority_fee,
+            } => {
+                // We should never risk getting gas too low errors because we set these vals in send_monitored_transaction
+                let prev_max_priority_fee = match &entry.request {
+                    TypedTransaction::Eip1559(inner) => inner
+                        .max_priority_fee_per_gas
+                        .unwrap_or(estimate_max_priority_fee),
+                    _ => estimate_max_priority_fee,
+                };
+                let prev_max_fee = entry.request.gas_price().unwrap_or(estimate_max_fee);
+
+                let estimate_base_fee = estimate_max_fee - estimate_max_priority_fee;
+                let original_base_fee = original_max_fee - original_priority_fee;
+                let prev_base_fee = prev_max_fee - prev_max_priority_fee;
+
+                // the fee the policy proposes for this round, computed from the fee
+                // the transaction was originally submitted with
+                let policy_priority_fee = policy(original_priority_fee, entry.escalations);
+                let policy_base_fee = policy(original_base_fee, entry.escalations);
+
+                // never go below the latest network estimate, and always clear the
+                // minimum 10% bump over the last fee we actually sent, regardless of
+                // what the policy proposes
+                let new_max_priority_fee = max(
+                    estimate_max_priority_fee,
+                    max(
+                        policy_priority_fee,
+                        self.increase_by_minimum(prev_max_priority_fee),
+                    ),
+                );
+                let new_base_fee = max(
+                    estimate_base_fee,
+                    max(policy_base_fee, self.increase_by_minimum(prev_base_fee)),
+                );
+                let new_max_fee = new_base_fee + new_max_priority_fee;
+
+                transaction_kind::set_eip1559_fees(
+                    &mut entry.request,
+                    new_max_fee,
+                    new_max_priority_fee,
+                );
+            }
+        }
     }
 
     // Rule: both the tip and the max fee must
@@ -252,4 +722,4 @@ where
         let increase = (value * 10) / 100u64;
         value + increase + 1 // add 1 here for rounding purposes
     }
-}
\ No newline at end of file
+}
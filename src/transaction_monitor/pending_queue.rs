@@ -0,0 +1,272 @@
+use ethers::types::{Address, U256};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use super::PendingTx;
+
+/// One sender's view of its own pending txs, ordered by nonce.
+#[derive(Default)]
+struct SenderQueue {
+    /// The next nonce the sender's account is expected to use on-chain, per
+    /// the last `eth_getTransactionCount` we made. Anything below this has
+    /// already landed (by us or otherwise) and is no longer our concern.
+    confirmed_nonce: U256,
+    entries: BTreeMap<U256, PendingTx>,
+}
+
+impl SenderQueue {
+    /// Nonces from `confirmed_nonce` with no gap, in ascending order — the
+    /// only txs it's safe to rebroadcast/escalate this round. Everything
+    /// else is `future`: waiting on a lower nonce to land first.
+    fn ready_nonces(&self) -> Vec<U256> {
+        let mut expected = self.confirmed_nonce;
+        let mut ready = Vec::new();
+        for nonce in self.entries.keys() {
+            if *nonce != expected {
+                break;
+            }
+            ready.push(*nonce);
+            expected += U256::one();
+        }
+        ready
+    }
+}
+
+/// A nonce-aware pending-transaction pool, modeled on a miner-style mempool:
+/// transactions are tracked per sender and partitioned into `ready` (can be
+/// escalated this round) and `future` (blocked behind a missing nonce), so a
+/// single stuck low-nonce tx no longer silently wedges every later one.
+#[derive(Default)]
+pub(crate) struct PendingQueue {
+    senders: HashMap<Address, SenderQueue>,
+}
+
+impl PendingQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `entry`, rejecting it if its nonce is more than `nonce_cap`
+    /// ahead of the sender's last known confirmed nonce, and evicting the
+    /// lowest-fee entry for that sender if already at `per_sender_limit`.
+    /// The lead (lowest) nonce is never evicted, since losing it would strand
+    /// every nonce queued after it.
+    pub fn insert(
+        &mut self,
+        sender: Address,
+        nonce: U256,
+        entry: PendingTx,
+        per_sender_limit: usize,
+        nonce_cap: u64,
+    ) -> Result<Option<PendingTx>, anyhow::Error> {
+        let queue = self.senders.entry(sender).or_insert_with(|| SenderQueue {
+            confirmed_nonce: nonce,
+            entries: BTreeMap::new(),
+        });
+
+        if nonce > queue.confirmed_nonce + nonce_cap {
+            anyhow::bail!(
+                "nonce {} is more than {} ahead of confirmed nonce {} for {:?}",
+                nonce,
+                nonce_cap,
+                queue.confirmed_nonce,
+                sender
+            );
+        }
+
+        let mut evicted = None;
+        if queue.entries.len() >= per_sender_limit {
+            let lowest_fee_nonce = queue
+                .entries
+                .iter()
+                .skip(1) // never evict the lead nonce
+                .min_by_key(|(_, tx)| tx.request.gas_price().unwrap_or_default())
+                .map(|(nonce, _)| *nonce);
+
+            match lowest_fee_nonce {
+                Some(evict_nonce) => evicted = queue.entries.remove(&evict_nonce),
+                None => anyhow::bail!("{:?}'s pending queue is full", sender),
+            }
+        }
+
+        queue.entries.insert(nonce, entry);
+        Ok(evicted)
+    }
+
+    /// Advances the sender's confirmed nonce and returns whatever entries it
+    /// has now superseded (included on-chain), so the caller can mark them
+    /// complete. This is also what lets a filled gap promote queued future
+    /// txs into the ready set, since readiness is derived from this value.
+    pub fn set_confirmed_nonce(&mut self, sender: Address, nonce: U256) -> Vec<PendingTx> {
+        let Some(queue) = self.senders.get_mut(&sender) else {
+            return Vec::new();
+        };
+
+        queue.confirmed_nonce = nonce;
+        let superseded_nonces: Vec<U256> = queue
+            .entries
+            .keys()
+            .filter(|n| **n < nonce)
+            .copied()
+            .collect();
+        superseded_nonces
+            .into_iter()
+            .filter_map(|n| queue.entries.remove(&n))
+            .collect()
+    }
+
+    /// Reinserts a tx reloaded from durable storage on startup, bypassing the
+    /// usual per-sender/nonce-cap checks since it was already accepted before
+    /// the restart.
+    pub fn restore(&mut self, sender: Address, nonce: U256, entry: PendingTx) {
+        self.senders
+            .entry(sender)
+            .or_insert_with(|| SenderQueue {
+                confirmed_nonce: nonce,
+                entries: BTreeMap::new(),
+            })
+            .entries
+            .insert(nonce, entry);
+    }
+
+    pub fn senders_with_pending_txs(&self) -> Vec<Address> {
+        self.senders
+            .iter()
+            .filter(|(_, queue)| !queue.entries.is_empty())
+            .map(|(sender, _)| *sender)
+            .collect()
+    }
+
+    /// Removes and returns every ready (sender, nonce, entry) across all
+    /// senders, in nonce order within each sender.
+    pub fn drain_ready(&mut self) -> Vec<(Address, U256, PendingTx)> {
+        let mut drained = Vec::new();
+        for (sender, queue) in self.senders.iter_mut() {
+            for nonce in queue.ready_nonces() {
+                if let Some(entry) = queue.entries.remove(&nonce) {
+                    drained.push((*sender, nonce, entry));
+                }
+            }
+        }
+        drained
+    }
+
+    /// Re-queues an entry after a round unless it has already been escalated
+    /// `max_escalations` times, in which case it's dropped and handed back to
+    /// the caller to persist as dropped.
+    pub fn requeue_or_expire(
+        &mut self,
+        sender: Address,
+        nonce: U256,
+        entry: PendingTx,
+        max_escalations: usize,
+    ) -> Option<PendingTx> {
+        if entry.escalations >= max_escalations {
+            return Some(entry);
+        }
+
+        self.senders
+            .entry(sender)
+            .or_insert_with(|| SenderQueue {
+                confirmed_nonce: nonce,
+                entries: BTreeMap::new(),
+            })
+            .entries
+            .insert(nonce, entry);
+        None
+    }
+
+    pub fn find(&self, id: Uuid) -> Option<&PendingTx> {
+        self.senders
+            .values()
+            .flat_map(|queue| queue.entries.values())
+            .find(|tx| tx.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transaction_monitor::transaction_kind::OriginalFees;
+    use crate::transaction_monitor::Speed;
+    use ethers::types::{transaction::eip2718::TypedTransaction, TransactionRequest, TxHash};
+
+    fn entry_with_gas_price(gas_price: u64) -> PendingTx {
+        let mut request = TransactionRequest::new();
+        request.gas_price = Some(U256::from(gas_price));
+
+        PendingTx {
+            tx_hash: TxHash::zero(),
+            request: TypedTransaction::Legacy(request),
+            priority: None,
+            id: Uuid::new_v4(),
+            escalations: 0,
+            original_fees: OriginalFees::Legacy {
+                gas_price: U256::from(gas_price),
+            },
+            policy_override: None,
+            speed: Speed::Standard,
+        }
+    }
+
+    #[test]
+    fn gap_blocked_nonces_stay_out_of_drain_ready() {
+        let sender = Address::from_low_u64_be(1);
+        let mut queue = PendingQueue::new();
+
+        queue
+            .insert(sender, U256::zero(), entry_with_gas_price(100), 16, 16)
+            .unwrap();
+        // nonce 1 is missing, so nonce 2 is stuck behind the gap
+        queue
+            .insert(sender, U256::from(2), entry_with_gas_price(100), 16, 16)
+            .unwrap();
+
+        let ready = queue.drain_ready();
+        assert_eq!(ready.len(), 1);
+        assert_eq!(ready[0].1, U256::zero());
+    }
+
+    #[test]
+    fn eviction_never_touches_the_lead_nonce() {
+        let sender = Address::from_low_u64_be(1);
+        let mut queue = PendingQueue::new();
+
+        // nonce 0 is the lead and also the lowest fee; it must survive even
+        // though it would otherwise be the first pick for eviction
+        queue
+            .insert(sender, U256::zero(), entry_with_gas_price(100), 2, 16)
+            .unwrap();
+        queue
+            .insert(sender, U256::one(), entry_with_gas_price(200), 2, 16)
+            .unwrap();
+        let evicted = queue
+            .insert(sender, U256::from(2), entry_with_gas_price(300), 2, 16)
+            .unwrap();
+
+        // nonce 1 (the only other candidate) gets evicted instead
+        assert!(evicted.is_some());
+        let ready = queue.drain_ready();
+        let remaining_nonces: Vec<U256> = ready.iter().map(|(_, nonce, _)| *nonce).collect();
+        assert!(remaining_nonces.contains(&U256::zero()));
+        assert!(!remaining_nonces.contains(&U256::one()));
+    }
+
+    #[test]
+    fn requeue_or_expire_drops_at_the_max_escalations_boundary() {
+        let sender = Address::from_low_u64_be(1);
+        let mut queue = PendingQueue::new();
+
+        let mut survives = entry_with_gas_price(100);
+        survives.escalations = 4;
+        assert!(queue
+            .requeue_or_expire(sender, U256::zero(), survives, 5)
+            .is_none());
+
+        let mut expires = entry_with_gas_price(100);
+        expires.escalations = 5;
+        assert!(queue
+            .requeue_or_expire(sender, U256::one(), expires, 5)
+            .is_some());
+    }
+}
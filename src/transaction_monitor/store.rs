@@ -0,0 +1,145 @@
+use ethers::types::{transaction::eip2718::TypedTransaction, TxHash};
+use sqlx::{MySql, Pool, Row};
+use std::str::FromStr;
+use uuid::Uuid;
+
+use super::transaction_kind::OriginalFees;
+use super::Speed;
+
+/// A monitored tx as reloaded from the database on startup.
+pub(crate) struct StoredTx {
+    pub id: Uuid,
+    pub tx_hash: TxHash,
+    pub request: TypedTransaction,
+    pub escalations: usize,
+    pub original_fees: OriginalFees,
+    pub speed: Speed,
+}
+
+/// Creates the backing table if it doesn't already exist. There's no
+/// migration tooling wired up yet, so this runs on every startup instead.
+pub(crate) async fn ensure_schema(pool: &Pool<MySql>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        CREATE TABLE IF NOT EXISTS monitored_transactions (
+            id CHAR(36) PRIMARY KEY,
+            tx_hash CHAR(66) NOT NULL,
+            request JSON NOT NULL,
+            original_fees JSON NOT NULL,
+            speed JSON NOT NULL,
+            escalations INT UNSIGNED NOT NULL DEFAULT 0,
+            status VARCHAR(16) NOT NULL DEFAULT 'pending',
+            updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP
+        )
+        "#,
+    )
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Writes the current state of a monitored tx, on both initial submission and
+/// every rebroadcast. `original_fees` and `speed` never change across
+/// rebroadcasts of the same tx, but are re-sent each time to keep the call
+/// shape simple.
+pub(crate) async fn upsert(
+    pool: &Pool<MySql>,
+    id: Uuid,
+    tx_hash: TxHash,
+    request: &TypedTransaction,
+    original_fees: &OriginalFees,
+    speed: Speed,
+    escalations: usize,
+) -> Result<(), sqlx::Error> {
+    let request_json = serde_json::to_string(request).expect("TypedTransaction always serializes");
+    let original_fees_json =
+        serde_json::to_string(original_fees).expect("OriginalFees always serializes");
+    let speed_json = serde_json::to_string(&speed).expect("Speed always serializes");
+
+    sqlx::query(
+        "INSERT INTO monitored_transactions (id, tx_hash, request, original_fees, speed, escalations, status)
+         VALUES (?, ?, ?, ?, ?, ?, 'pending')
+         ON DUPLICATE KEY UPDATE tx_hash = VALUES(tx_hash), request = VALUES(request), escalations = VALUES(escalations)",
+    )
+    .bind(id.to_string())
+    .bind(format!("{:?}", tx_hash))
+    .bind(request_json)
+    .bind(original_fees_json)
+    .bind(speed_json)
+    .bind(escalations as u32)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn mark_complete(pool: &Pool<MySql>, id: Uuid) -> Result<(), sqlx::Error> {
+    set_status(pool, id, "complete").await
+}
+
+pub(crate) async fn mark_dropped(pool: &Pool<MySql>, id: Uuid) -> Result<(), sqlx::Error> {
+    set_status(pool, id, "dropped").await
+}
+
+async fn set_status(pool: &Pool<MySql>, id: Uuid, status: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE monitored_transactions SET status = ? WHERE id = ?")
+        .bind(status)
+        .bind(id.to_string())
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Reloads every tx that hadn't reached a terminal status before the last
+/// shutdown, so the monitor can resume escalating it.
+pub(crate) async fn load_pending(pool: &Pool<MySql>) -> Result<Vec<StoredTx>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT id, tx_hash, request, original_fees, speed, escalations FROM monitored_transactions WHERE status = 'pending'",
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut stored = Vec::with_capacity(rows.len());
+    for row in rows {
+        let id: String = row.try_get("id")?;
+        let tx_hash: String = row.try_get("tx_hash")?;
+        let request_json: String = row.try_get("request")?;
+        let original_fees_json: String = row.try_get("original_fees")?;
+        let speed_json: String = row.try_get("speed")?;
+        let escalations: u32 = row.try_get("escalations")?;
+
+        stored.push(StoredTx {
+            id: Uuid::parse_str(&id).map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+            tx_hash: TxHash::from_str(&tx_hash).map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+            request: serde_json::from_str(&request_json)
+                .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+            original_fees: serde_json::from_str(&original_fees_json)
+                .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+            speed: serde_json::from_str(&speed_json)
+                .map_err(|err| sqlx::Error::Decode(Box::new(err)))?,
+            escalations: escalations as usize,
+        });
+    }
+
+    Ok(stored)
+}
+
+/// Looks up the last known status of a tx that's no longer being actively
+/// monitored (i.e. not found in the in-memory queue). Returns `(mined, hash)`.
+pub(crate) async fn get_terminal_status(
+    pool: &Pool<MySql>,
+    id: Uuid,
+) -> Result<Option<(bool, String)>, sqlx::Error> {
+    let row = sqlx::query("SELECT tx_hash, status FROM monitored_transactions WHERE id = ?")
+        .bind(id.to_string())
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|row| {
+        let tx_hash: String = row.get("tx_hash");
+        let status: String = row.get("status");
+        (status == "complete", tx_hash)
+    }))
+}
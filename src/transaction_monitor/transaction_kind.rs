@@ -0,0 +1,75 @@
+use ethers::types::{
+    transaction::eip2718::TypedTransaction, Chain, U256,
+};
+use serde::{Deserialize, Serialize};
+
+/// Which transaction envelope to use when a caller doesn't set gas fields
+/// explicitly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TxKind {
+    /// `gasPrice`-based, for chains without an EIP-1559 fee market (and
+    /// EIP-2930 access-list transactions, which bump the same way).
+    Legacy,
+    Eip1559,
+}
+
+/// Whether `chain` is known to support EIP-1559 fee markets. Anything not
+/// listed here defaults to `true`, since 1559 is the common case among EVM
+/// chains today.
+pub fn supports_eip1559(chain: Chain) -> bool {
+    !matches!(
+        chain,
+        Chain::BinanceSmartChain
+            | Chain::BinanceSmartChainTestnet
+            | Chain::Celo
+            | Chain::CeloAlfajores
+            | Chain::CeloBaklava
+    )
+}
+
+/// The fee fields a transaction was originally submitted with — the base the
+/// escalation policy computes each round's bump from. Persisted alongside the
+/// tx so a restart doesn't lose it to whatever fee the tx has since escalated
+/// to.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum OriginalFees {
+    Legacy { gas_price: U256 },
+    Eip1559 { max_fee: U256, priority_fee: U256 },
+}
+
+impl OriginalFees {
+    pub fn from_typed_transaction(tx: &TypedTransaction) -> Self {
+        match tx {
+            TypedTransaction::Eip1559(inner) => OriginalFees::Eip1559 {
+                max_fee: inner.max_fee_per_gas.unwrap_or_default(),
+                priority_fee: inner.max_priority_fee_per_gas.unwrap_or_default(),
+            },
+            TypedTransaction::Legacy(inner) => OriginalFees::Legacy {
+                gas_price: inner.gas_price.unwrap_or_default(),
+            },
+            TypedTransaction::Eip2930(inner) => OriginalFees::Legacy {
+                gas_price: inner.tx.gas_price.unwrap_or_default(),
+            },
+        }
+    }
+}
+
+/// Sets `gas_price` on whichever legacy-style (`Legacy`/`Eip2930`) variant
+/// `tx` is. No-op for `Eip1559`.
+pub fn set_gas_price(tx: &mut TypedTransaction, gas_price: U256) {
+    match tx {
+        TypedTransaction::Legacy(inner) => inner.gas_price = Some(gas_price),
+        TypedTransaction::Eip2930(inner) => inner.tx.gas_price = Some(gas_price),
+        TypedTransaction::Eip1559(_) => {}
+    }
+}
+
+/// Sets `max_fee_per_gas`/`max_priority_fee_per_gas` on `tx` if it's the
+/// `Eip1559` variant. No-op otherwise.
+pub fn set_eip1559_fees(tx: &mut TypedTransaction, max_fee: U256, priority_fee: U256) {
+    if let TypedTransaction::Eip1559(inner) = tx {
+        inner.max_fee_per_gas = Some(max_fee);
+        inner.max_priority_fee_per_gas = Some(priority_fee);
+    }
+}